@@ -102,6 +102,39 @@ fn iter_to_env(list: &Vec<(String, String)>) {
         std::env::set_var(key, value);
     }
 }
+
+/// Reads .env file and stores the key value pairs as environment variables,
+/// but only for keys that are not already set in the environment.
+///
+/// This lets a `.env` file be layered underneath the real environment: values
+/// already exported by the shell or CI keep winning over the file.
+/// ```rust
+/// fn main() {
+///    let _ = stupid_simple_dotenv::to_env_if_absent(); // fills in only the missing keys
+/// }
+/// ```
+pub fn to_env_if_absent() -> Result<(), SimpleEnvError> {
+    match read(".env") {
+        Ok(list) => {
+            iter_to_env_if_absent(&list);
+            Ok(())
+        }
+        Err(e) => {
+            e.list.as_ref().map(iter_to_env_if_absent);
+            Err(e)
+        }
+    }
+}
+
+fn iter_to_env_if_absent(list: &Vec<(String, String)>) {
+    for line in list {
+        let (key, value) = (&line.0, &line.1);
+        if std::env::var(key).is_err() {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
 /// Reads .env file to a vector of key value pairs tuples.
 /// ```rust
 /// fn main() {
@@ -135,6 +168,19 @@ pub fn file_to_env<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
+/// Reads key value pairs from a file and stores them as environment variables,
+/// but only for keys that are not already set in the environment.
+pub fn file_to_env_if_absent<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+    let list = read(path)?;
+    for line in list {
+        let (key, value) = (line.0, line.1);
+        if std::env::var(&key).is_err() {
+            std::env::set_var(key, value);
+        }
+    }
+    Ok(())
+}
+
 /// Reads key value pairs from a file and returns a vector of tuples.
 /// ```rust
 /// fn main() {
@@ -161,6 +207,20 @@ pub fn get_or(key: &str, default: &str) -> String {
     std::env::var(key).unwrap_or_else(|_| default.to_owned())
 }
 
+/// Try to get the value of an environment variable as an [`OsString`](std::ffi::OsString).
+/// If the variable is not present in the environment, `default` is returned.
+/// Unlike [`get_or`], which returns `default` when the value is not valid UTF-8,
+/// this reads the value through [`std::env::var_os`], so a value that is not valid
+/// UTF-8 is returned as it is.
+/// ```rust
+/// fn main() {
+///     let value = stupid_simple_dotenv::get_os_or("key_not_here", "default_key");
+///     assert_eq!("default_key", value.to_str().unwrap());
+/// }
+pub fn get_os_or(key: &str, default: &str) -> std::ffi::OsString {
+    std::env::var_os(key).unwrap_or_else(|| default.into())
+}
+
 fn read<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>, SimpleEnvError> {
     let f = File::open(path)?;
     let lines = std::io::BufReader::new(f).lines();
@@ -170,28 +230,33 @@ fn read<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>, SimpleEnvError
 fn parse(
     lines: impl Iterator<Item = Result<String, std::io::Error>>,
 ) -> Result<Vec<(String, String)>, SimpleEnvError> {
+    parse_with_quotes(lines).map(drop_quote_flag)
+}
+
+fn drop_quote_flag(list: Vec<(String, String, bool)>) -> Vec<(String, String)> {
+    list.into_iter().map(|(k, v, _)| (k, v)).collect()
+}
+
+fn parse_with_quotes(
+    lines: impl Iterator<Item = Result<String, std::io::Error>>,
+) -> Result<Vec<(String, String, bool)>, SimpleEnvError> {
     let mut error_lines = Vec::new();
     let mut num_error_lines = 0;
     let mut list = Vec::new();
-    let lines = lines;
-    for (col, line) in lines.enumerate() {
-        let line = line?;
-        let line = line.trim();
-        if line.starts_with('#') || line.is_empty() {
-            continue;
-        }
-        let parsed = match parse_line(line) {
-            Ok(parsed) => parsed,
+    let mut entries = EntryIter::new(lines);
+    loop {
+        match entries.try_next() {
+            Ok(Some(entry)) => list.push(entry),
+            Ok(None) => break,
+            Err(e) if e.kind == "io" => return Err(e),
             Err(e) => {
                 num_error_lines += 1;
                 if error_lines.len() < 10 {
-                    error_lines.push(format!("Error in Line {col}: {e}"));
+                    error_lines.push(e.message.clone());
                 }
-                error_lines.push(format!("Error in Line {col}: {e}"));
-                continue;
+                error_lines.push(e.message);
             }
-        };
-        list.push((parsed.0.to_owned(), parsed.1.to_owned()));
+        }
     }
     if error_lines.is_empty() {
         Ok(list)
@@ -205,12 +270,226 @@ fn parse(
         Err(SimpleEnvError {
             kind: "LinesError".to_string(),
             message: error_lines.join("\n"),
-            list: Some(list),
+            list: Some(drop_quote_flag(list)),
         })
     }
 }
 
-fn parse_line(s: &str) -> Result<(&str, &str), Box<dyn Error>> {
+/// Reads key/value entries lazily, one at a time, instead of eagerly buffering the
+/// whole file. Returned by [`iter_file`] / [`iter_env`]. Unlike [`to_vec`] and friends,
+/// which aggregate every bad line into one [`SimpleEnvError`], `try_next` surfaces each
+/// entry's error as soon as it's reached, so callers can correlate it with the exact
+/// line and decide per-key what to do (skip it, stop, only set it if absent, ...).
+pub struct Iter<L: Iterator<Item = Result<String, std::io::Error>>> {
+    entries: EntryIter<L>,
+}
+
+impl<L: Iterator<Item = Result<String, std::io::Error>>> Iter<L> {
+    /// Returns the next key/value entry, `Ok(None)` at end of input, or the parse
+    /// error for the current entry. A call after an `Err` resumes right after the
+    /// offending line, so the caller can simply keep calling `try_next` to skip it.
+    pub fn try_next(&mut self) -> Result<Option<(String, String)>, SimpleEnvError> {
+        self.entries
+            .try_next()
+            .map(|entry| entry.map(|(key, value, _)| (key, value)))
+    }
+}
+
+/// Shared line-consuming engine behind [`parse_with_quotes`] and the public [`Iter`]:
+/// joins multi-line quoted values and turns each logical line into one entry.
+struct EntryIter<L: Iterator<Item = Result<String, std::io::Error>>> {
+    lines: std::iter::Enumerate<L>,
+}
+
+impl<L: Iterator<Item = Result<String, std::io::Error>>> EntryIter<L> {
+    fn new(lines: L) -> Self {
+        EntryIter {
+            lines: lines.enumerate(),
+        }
+    }
+
+    fn try_next(&mut self) -> Result<Option<(String, String, bool)>, SimpleEnvError> {
+        loop {
+            let (col, line) = match self.lines.next() {
+                Some(pair) => pair,
+                None => return Ok(None),
+            };
+            let line = line?;
+            let trimmed = line.trim();
+            if trimmed.starts_with('#') || trimmed.is_empty() {
+                continue;
+            }
+            // A value can open a quote without closing it on the same physical
+            // line (a PEM key, a JSON blob, ...). Keep pulling lines into the
+            // buffer, re-joined with '\n', until the quote closes or EOF hits.
+            let mut buffer = trimmed.to_owned();
+            loop {
+                match parse_line(&buffer) {
+                    Ok(parsed) => return Ok(Some(parsed)),
+                    Err(e) if e.is::<UnterminatedQuote>() => match self.lines.next() {
+                        Some((_, next_line)) => {
+                            buffer.push('\n');
+                            buffer.push_str(&next_line?);
+                        }
+                        None => {
+                            return Err(SimpleEnvError {
+                                kind: "LinesError".to_string(),
+                                message: format!(
+                                    "Error in Line {col}: unterminated quote starting at line {col}"
+                                ),
+                                list: None,
+                            });
+                        }
+                    },
+                    Err(e) => {
+                        return Err(SimpleEnvError {
+                            kind: "LinesError".to_string(),
+                            message: format!("Error in Line {col}: {e}"),
+                            list: None,
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Lazily iterates the key/value entries of a file. See [`Iter`].
+pub fn iter_file<P: AsRef<Path>>(
+    path: P,
+) -> Result<Iter<std::io::Lines<std::io::BufReader<File>>>, SimpleEnvError> {
+    let f = File::open(path)?;
+    Ok(Iter {
+        entries: EntryIter::new(std::io::BufReader::new(f).lines()),
+    })
+}
+
+/// Lazily iterates the key/value entries of `.env`. See [`Iter`].
+pub fn iter_env() -> Result<Iter<std::io::Lines<std::io::BufReader<File>>>, SimpleEnvError> {
+    iter_file(".env")
+}
+
+/// Reads .env file and expands `${VAR}` / `$VAR` references found in the values.
+///
+/// A reference resolves first against keys already parsed earlier in the same file,
+/// then against the process environment via [`std::env::var`], and finally to an
+/// empty string. `${NAME:-default}` falls back to `default` when `NAME` is unset or
+/// empty, `${NAME-default}` only when `NAME` is unset, and `$$` yields a literal `$`.
+/// Values that were single-quoted in the file are left completely literal.
+/// ```rust
+/// fn main() {
+///    std::env::set_var("HOME", "/home/user");
+///    let _ = stupid_simple_dotenv::to_env_expand(); // reads .env file, expands $VAR references
+/// }
+/// ```
+pub fn to_env_expand() -> Result<(), SimpleEnvError> {
+    match read_expand(".env") {
+        Ok(list) => {
+            iter_to_env(&list);
+            Ok(())
+        }
+        Err(e) => {
+            e.list.as_ref().map(iter_to_env);
+            Err(e)
+        }
+    }
+}
+
+fn read_expand<P: AsRef<Path>>(path: P) -> Result<Vec<(String, String)>, SimpleEnvError> {
+    let f = File::open(path)?;
+    let lines = std::io::BufReader::new(f).lines();
+    let list = parse_with_quotes(lines)?;
+    Ok(expand_list(list))
+}
+
+fn expand_list(list: Vec<(String, String, bool)>) -> Vec<(String, String)> {
+    let mut resolved: Vec<(String, String)> = Vec::with_capacity(list.len());
+    for (key, value, is_single_quoted) in list {
+        let value = if is_single_quoted {
+            value
+        } else {
+            expand_value(&value, &resolved)
+        };
+        resolved.push((key, value));
+    }
+    resolved
+}
+
+fn lookup_var(name: &str, resolved: &[(String, String)]) -> Option<String> {
+    resolved
+        .iter()
+        .rev()
+        .find(|(k, _)| k == name)
+        .map(|(_, v)| v.clone())
+        .or_else(|| std::env::var(name).ok())
+}
+
+fn expand_value(value: &str, resolved: &[(String, String)]) -> String {
+    let chars: Vec<char> = value.chars().collect();
+    let mut out = String::with_capacity(value.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '$' {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        if chars.get(i + 1) == Some(&'$') {
+            out.push('$');
+            i += 2;
+        } else if chars.get(i + 1) == Some(&'{') {
+            match chars[i + 2..].iter().position(|&c| c == '}') {
+                Some(end) => {
+                    let inner: String = chars[i + 2..i + 2 + end].iter().collect();
+                    out.push_str(&expand_braced(&inner, resolved));
+                    i = i + 2 + end + 1;
+                }
+                None => {
+                    // No closing brace: treat the rest literally.
+                    out.push_str(&chars[i..].iter().collect::<String>());
+                    break;
+                }
+            }
+        } else if matches!(chars.get(i + 1), Some(c) if c.is_alphabetic() || *c == '_') {
+            let mut j = i + 1;
+            while matches!(chars.get(j), Some(c) if c.is_alphanumeric() || *c == '_') {
+                j += 1;
+            }
+            let name: String = chars[i + 1..j].iter().collect();
+            out.push_str(&lookup_var(&name, resolved).unwrap_or_default());
+            i = j;
+        } else {
+            out.push('$');
+            i += 1;
+        }
+    }
+    out
+}
+
+fn expand_braced(inner: &str, resolved: &[(String, String)]) -> String {
+    if let Some((name, default)) = inner.split_once(":-") {
+        lookup_var(name, resolved)
+            .filter(|v| !v.is_empty())
+            .unwrap_or_else(|| expand_value(default, resolved))
+    } else if let Some((name, default)) = inner.split_once('-') {
+        lookup_var(name, resolved).unwrap_or_else(|| expand_value(default, resolved))
+    } else {
+        lookup_var(inner, resolved).unwrap_or_default()
+    }
+}
+
+#[derive(Debug)]
+struct UnterminatedQuote;
+
+impl Display for UnterminatedQuote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unterminated quote")
+    }
+}
+
+impl Error for UnterminatedQuote {}
+
+fn parse_line(s: &str) -> Result<(String, String, bool), Box<dyn Error>> {
     let mut name_begin: usize = 0;
     let mut name_end: usize = 0;
     let mut value_begin: usize = 0;
@@ -218,6 +497,7 @@ fn parse_line(s: &str) -> Result<(&str, &str), Box<dyn Error>> {
     let mut in_name = true;
     let mut in_value = false;
     let mut quotes = 'f';
+    let mut value_quote = 'f';
     let mut must_trim = false;
     for (pos, c) in s.char_indices() {
         match c {
@@ -243,6 +523,7 @@ fn parse_line(s: &str) -> Result<(&str, &str), Box<dyn Error>> {
                     }
                     if in_value {
                         value_begin = pos + 1;
+                        value_quote = c;
                     }
                 }
             }
@@ -286,7 +567,9 @@ fn parse_line(s: &str) -> Result<(&str, &str), Box<dyn Error>> {
             }
         }
     }
-    if value_begin == 0 || name_end == 0 {
+    if in_value && quotes != 'f' {
+        Err(Box::new(UnterminatedQuote))
+    } else if value_begin == 0 || name_end == 0 {
         Err(format!("No name or value in '{s}'").into())
     } else if value_begin == 0 {
         Err("No value".into())
@@ -297,10 +580,72 @@ fn parse_line(s: &str) -> Result<(&str, &str), Box<dyn Error>> {
                 value_end = value_begin + s.trim_end().len() - 1;
             }
         }
-        Ok((&s[name_begin..=name_end], &s[value_begin..=value_end]))
+        let raw_value = &s[value_begin..=value_end];
+        let value = if value_quote == '"' || value_quote == '`' {
+            unescape(raw_value)?
+        } else {
+            raw_value.to_owned()
+        };
+        let name = strip_export_prefix(&s[name_begin..=name_end]).to_owned();
+        Ok((name, value, value_quote == '\''))
     }
 }
 
+/// `.env` files are often also `source`-able shell scripts whose lines begin with
+/// `export `. Strip that prefix so `export API_KEY=xyz` yields the key `API_KEY`.
+fn strip_export_prefix(name: &str) -> &str {
+    match name.strip_prefix("export") {
+        Some(rest) if rest.starts_with(char::is_whitespace) => rest.trim_start(),
+        _ => name,
+    }
+}
+
+/// Decodes `\n`, `\t`, `\r`, `\\`, `\"`, `` \` ``, `\0`, `\xNN` and `\u{...}` escapes found in a
+/// `"`- or `` ` ``-quoted value. Single-quoted and unquoted values never reach here.
+/// `parse_line` ends a quoted value at the first matching quote character, backslash
+/// or not, so `\"` is decoded only in a `` ` ``-quoted value and `` \` `` only in a
+/// `"`-quoted one; in its own quotes such an escape leaves a trailing backslash and
+/// the line is reported as an error.
+fn unescape(value: &str) -> Result<String, Box<dyn Error>> {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some('\\') => out.push('\\'),
+            Some('"') => out.push('"'),
+            Some('`') => out.push('`'),
+            Some('0') => out.push('\0'),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                let byte = u8::from_str_radix(&hex, 16)
+                    .map_err(|_| format!("invalid \\x escape in '{value}'"))?;
+                out.push(byte as char);
+            }
+            Some('u') => {
+                if chars.next() != Some('{') {
+                    return Err(format!("invalid \\u escape in '{value}'").into());
+                }
+                let hex: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                let code = u32::from_str_radix(&hex, 16)
+                    .map_err(|_| format!("invalid \\u escape in '{value}'"))?;
+                let ch = char::from_u32(code)
+                    .ok_or_else(|| format!("invalid \\u escape in '{value}'"))?;
+                out.push(ch);
+            }
+            Some(other) => return Err(format!("invalid escape '\\{other}' in '{value}'").into()),
+            None => return Err(format!("trailing backslash in '{value}'").into()),
+        }
+    }
+    Ok(out)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -324,29 +669,60 @@ mod tests {
         }
     }
 
+    fn owned(name: &str, value: &str, is_single_quoted: bool) -> (String, String, bool) {
+        (name.to_owned(), value.to_owned(), is_single_quoted)
+    }
+
     #[test]
     fn test_parse_line_new() {
-        assert_eq!(parse_line("FOO=BAR").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("\"FOO\"=\"BAR\"").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO = BAR").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO=\"BAR\"").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO='BAR'").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO=`BAR`").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO=\t `BAR`").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO\t=\t `BAR`").unwrap(), ("FOO", "BAR"));
-        assert_eq!(parse_line("FOO\t=\t ` BAR`").unwrap(), ("FOO", " BAR"));
+        assert_eq!(parse_line("FOO=BAR").unwrap(), owned("FOO", "BAR", false));
+        assert_eq!(
+            parse_line("\"FOO\"=\"BAR\"").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO = BAR").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO=\"BAR\"").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO='BAR'").unwrap(),
+            owned("FOO", "BAR", true)
+        );
+        assert_eq!(
+            parse_line("FOO=`BAR`").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO=\t `BAR`").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO\t=\t `BAR`").unwrap(),
+            owned("FOO", "BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO\t=\t ` BAR`").unwrap(),
+            owned("FOO", " BAR", false)
+        );
         assert_eq!(
             parse_line("FOO\t=\t ` BAR`#comment").unwrap(),
-            ("FOO", " BAR")
+            owned("FOO", " BAR", false)
+        );
+        assert_eq!(
+            parse_line("FOO\t=\t ` BAR `").unwrap(),
+            owned("FOO", " BAR ", false)
         );
-        assert_eq!(parse_line("FOO\t=\t ` BAR `").unwrap(), ("FOO", " BAR "));
         assert_eq!(
             parse_line("FOO\t   =   \t ` BAR `").unwrap(),
-            ("FOO", " BAR ")
+            owned("FOO", " BAR ", false)
         );
         assert_eq!(
             parse_line(" FOO\t   =   \t ` BAR `").unwrap(),
-            (" FOO", " BAR ")
+            owned(" FOO", " BAR ", false)
         );
 
         assert_eq!(true, matches!(parse_line(" FOO\t   = "), Result::Err(_)));
@@ -354,6 +730,69 @@ mod tests {
         assert_eq!(true, matches!(parse_line("="), Result::Err(_)));
     }
 
+    #[test]
+    fn test_parse_line_escapes_in_double_quotes() {
+        assert_eq!(
+            parse_line("GREETING=\"line1\\nline2\"").unwrap(),
+            owned("GREETING", "line1\nline2", false)
+        );
+        assert_eq!(
+            parse_line("TAB=\"a\\tb\"").unwrap(),
+            owned("TAB", "a\tb", false)
+        );
+        assert_eq!(
+            parse_line("SLASH=\"a\\\\b\"").unwrap(),
+            owned("SLASH", "a\\b", false)
+        );
+        assert_eq!(
+            parse_line("HEX=\"\\x41\"").unwrap(),
+            owned("HEX", "A", false)
+        );
+        assert_eq!(
+            parse_line("UNI=\"\\u{1F600}\"").unwrap(),
+            owned("UNI", "\u{1F600}", false)
+        );
+        // Single-quoted values are never escaped.
+        assert_eq!(
+            parse_line("RAW='a\\nb'").unwrap(),
+            owned("RAW", "a\\nb", true)
+        );
+        assert!(matches!(parse_line("BAD=\"\\q\""), Result::Err(_)));
+    }
+
+    #[test]
+    fn test_expand_value() {
+        let resolved = vec![("HOME".to_owned(), "/home/user".to_owned())];
+        assert_eq!(expand_value("${HOME}/bin", &resolved), "/home/user/bin");
+        assert_eq!(expand_value("$HOME/bin", &resolved), "/home/user/bin");
+        assert_eq!(expand_value("$$HOME", &resolved), "$HOME");
+        assert_eq!(expand_value("${MISSING}", &resolved), "");
+        assert_eq!(
+            expand_value("${MISSING:-fallback}", &resolved),
+            "fallback"
+        );
+        assert_eq!(expand_value("${HOME:-fallback}", &resolved), "/home/user");
+        assert_eq!(expand_value("${MISSING-fallback}", &resolved), "fallback");
+    }
+
+    #[test]
+    fn test_expand_list_skips_single_quoted() {
+        let list = vec![
+            ("HOME".to_owned(), "/home/user".to_owned(), false),
+            ("PATH".to_owned(), "${HOME}/bin".to_owned(), false),
+            ("LITERAL".to_owned(), "${HOME}/bin".to_owned(), true),
+        ];
+        let expanded = expand_list(list);
+        assert_eq!(
+            expanded,
+            vec![
+                ("HOME".to_owned(), "/home/user".to_owned()),
+                ("PATH".to_owned(), "/home/user/bin".to_owned()),
+                ("LITERAL".to_owned(), "${HOME}/bin".to_owned()),
+            ]
+        );
+    }
+
     #[test]
     fn test_parse() {
         let env_sim = r#"
@@ -389,4 +828,114 @@ Foo7=BA😀R7 #comment
         );
         assert_eq!(list, list2);
     }
+
+    #[test]
+    fn test_parse_multiline_quoted_value() {
+        let env_sim = "CERT=\"-----BEGIN CERT-----\nline1\nline2\n-----END CERT-----\"\nAFTER=VALUE";
+        let lines = env_sim.lines().map(|s| Ok(s.to_owned()));
+        let list = parse(lines).unwrap();
+        assert_eq!(
+            list,
+            vec![
+                (
+                    "CERT".to_owned(),
+                    "-----BEGIN CERT-----\nline1\nline2\n-----END CERT-----".to_owned()
+                ),
+                ("AFTER".to_owned(), "VALUE".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_unterminated_quote_reports_error() {
+        let env_sim = "FOO=BAR\nCERT=\"-----BEGIN CERT-----\nline1";
+        let lines = env_sim.lines().map(|s| Ok(s.to_owned()));
+        let err = parse(lines).unwrap_err();
+        assert_eq!(err.kind, "LinesError");
+        assert!(err
+            .message
+            .contains("unterminated quote starting at line 1"));
+    }
+
+    #[test]
+    fn test_iter_to_env_if_absent_keeps_existing_value() {
+        std::env::set_var("STUPID_SIMPLE_DOTENV_TEST_ABSENT", "from_shell");
+        let list = vec![(
+            "STUPID_SIMPLE_DOTENV_TEST_ABSENT".to_owned(),
+            "from_file".to_owned(),
+        )];
+        iter_to_env_if_absent(&list);
+        assert_eq!(
+            std::env::var("STUPID_SIMPLE_DOTENV_TEST_ABSENT").unwrap(),
+            "from_shell"
+        );
+        std::env::remove_var("STUPID_SIMPLE_DOTENV_TEST_ABSENT");
+    }
+
+    #[test]
+    fn test_entry_iter_yields_entries_then_none() {
+        let env_sim = "FOO=BAR\nBAZ=QUX\n";
+        let lines = env_sim.lines().map(|s| Ok(s.to_owned()));
+        let mut iter = EntryIter::new(lines);
+        assert_eq!(
+            iter.try_next().unwrap(),
+            Some(("FOO".to_owned(), "BAR".to_owned(), false))
+        );
+        assert_eq!(
+            iter.try_next().unwrap(),
+            Some(("BAZ".to_owned(), "QUX".to_owned(), false))
+        );
+        assert_eq!(iter.try_next().unwrap(), None);
+    }
+
+    #[test]
+    fn test_entry_iter_surfaces_error_then_resumes() {
+        let env_sim = "FOO=BAR\nerror=\nBAZ=QUX\n";
+        let lines = env_sim.lines().map(|s| Ok(s.to_owned()));
+        let mut iter = EntryIter::new(lines);
+        assert_eq!(
+            iter.try_next().unwrap(),
+            Some(("FOO".to_owned(), "BAR".to_owned(), false))
+        );
+        let err = iter.try_next().unwrap_err();
+        assert_eq!(err.kind, "LinesError");
+        assert!(err.message.starts_with("Error in Line 1:"));
+        assert_eq!(
+            iter.try_next().unwrap(),
+            Some(("BAZ".to_owned(), "QUX".to_owned(), false))
+        );
+        assert_eq!(iter.try_next().unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_line_strips_export_prefix() {
+        assert_eq!(
+            parse_line("export API_KEY=xyz").unwrap(),
+            owned("API_KEY", "xyz", false)
+        );
+        assert_eq!(
+            parse_line("export\tAPI_KEY=xyz").unwrap(),
+            owned("API_KEY", "xyz", false)
+        );
+        // No whitespace after "export" means it's part of the key, not the keyword.
+        assert_eq!(
+            parse_line("exportFOO=BAR").unwrap(),
+            owned("exportFOO", "BAR", false)
+        );
+    }
+
+    #[test]
+    fn test_get_os_or() {
+        std::env::remove_var("STUPID_SIMPLE_DOTENV_TEST_OS_OR");
+        assert_eq!(
+            get_os_or("STUPID_SIMPLE_DOTENV_TEST_OS_OR", "fallback"),
+            std::ffi::OsString::from("fallback")
+        );
+        std::env::set_var("STUPID_SIMPLE_DOTENV_TEST_OS_OR", "value");
+        assert_eq!(
+            get_os_or("STUPID_SIMPLE_DOTENV_TEST_OS_OR", "fallback"),
+            std::ffi::OsString::from("value")
+        );
+        std::env::remove_var("STUPID_SIMPLE_DOTENV_TEST_OS_OR");
+    }
 }